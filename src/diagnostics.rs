@@ -0,0 +1,44 @@
+/// Renders a diagnostic as the offending source line with a caret
+/// underline beneath the `from..=to` span, followed by `message`.
+pub fn render_error(source: &str, from: usize, to: usize, message: &str) -> String {
+    let to = to.max(from);
+    let mut line_start = 0;
+    let mut line_number = 1;
+    let mut line = "";
+    for candidate in source.split_inclusive('\n') {
+        let line_end = line_start + candidate.len();
+        if from < line_end || line_end == source.len() {
+            line = candidate.trim_end_matches('\n');
+            break;
+        }
+        line_start = line_end;
+        line_number += 1;
+    }
+
+    let column = from.saturating_sub(line_start);
+    let underline_len = (to - from + 1).min(line.len().saturating_sub(column).max(1));
+    let gutter = format!("{line_number} | ");
+    let pad = " ".repeat(gutter.len() + column);
+    let carets = "^".repeat(underline_len);
+
+    format!("{gutter}{line}\n{pad}{carets}\n{pad}{message}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_at_the_offending_span() {
+        let source = "1 + @";
+        let rendered = render_error(source, 4, 4, "unexpected token");
+        assert_eq!(rendered, "1 | 1 + @\n        ^\n        unexpected token");
+    }
+
+    #[test]
+    fn finds_the_line_of_a_multi_line_span() {
+        let source = "1 + 1\n2 + @";
+        let rendered = render_error(source, 10, 10, "unexpected token");
+        assert_eq!(rendered, "2 | 2 + @\n        ^\n        unexpected token");
+    }
+}
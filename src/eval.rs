@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+
+use crate::parser::{BinaryVariant, Expr};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Function {
+        param: String,
+        body: Expr,
+        source: String,
+    },
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Int(value) => write!(f, "{value}"),
+            Value::Float(value) => write!(f, "{value}"),
+            Value::Bool(value) => write!(f, "{value}"),
+            Value::Function { param, .. } => write!(f, "<function({param})>"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Error {
+    pub from: usize,
+    pub to: usize,
+    pub message: String,
+    pub source: String,
+}
+
+impl Error {
+    /// Renders against the source the span was produced from (the line a
+    /// function was defined on, for errors raised inside its body), not
+    /// necessarily the line currently being evaluated.
+    pub fn render(&self) -> String {
+        crate::diagnostics::render_error(&self.source, self.from, self.to, &self.message)
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct Environment {
+    variables: HashMap<String, Value>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.variables.get(name)
+    }
+
+    pub fn set(&mut self, name: String, value: Value) {
+        self.variables.insert(name, value);
+    }
+
+    pub fn child(&self) -> Environment {
+        self.clone()
+    }
+}
+
+pub struct Evaluator<'env> {
+    env: &'env mut Environment,
+    source: String,
+}
+
+impl<'env> Evaluator<'env> {
+    pub fn new(env: &'env mut Environment, source: impl Into<String>) -> Self {
+        Self {
+            env,
+            source: source.into(),
+        }
+    }
+
+    fn error(&self, from: usize, to: usize, message: String) -> Error {
+        Error {
+            from,
+            to,
+            message,
+            source: self.source.clone(),
+        }
+    }
+
+    pub fn eval(&mut self, expr: &Expr) -> Result<Value, Error> {
+        match expr {
+            Expr::Int(value, _, _) => Ok(Value::Int(*value)),
+            Expr::Float(value, _, _) => Ok(Value::Float(*value)),
+            Expr::Id(name, from, to) => self
+                .env
+                .get(name)
+                .cloned()
+                .ok_or_else(|| self.error(*from, *to, format!("unknown identifier '{name}'"))),
+            Expr::Lambda(param, body, _, _) => Ok(Value::Function {
+                param: param.clone(),
+                body: (**body).clone(),
+                source: self.source.clone(),
+            }),
+            Expr::Binary(variant, left, right, from, to) => {
+                self.eval_binary(variant, left, right, *from, *to)
+            }
+        }
+    }
+
+    fn eval_binary(
+        &mut self,
+        variant: &BinaryVariant,
+        left: &Expr,
+        right: &Expr,
+        from: usize,
+        to: usize,
+    ) -> Result<Value, Error> {
+        match variant {
+            BinaryVariant::Fn => {
+                let value = self.eval(right)?;
+                if let Expr::Id(name, _, _) = left {
+                    self.env.set(name.clone(), value.clone());
+                }
+                Ok(value)
+            }
+            BinaryVariant::Call => {
+                let callee = self.eval(left)?;
+                let Value::Function {
+                    param,
+                    body,
+                    source,
+                } = callee
+                else {
+                    return Err(self.error(
+                        from,
+                        to,
+                        "cannot call a value that is not a function".to_string(),
+                    ));
+                };
+                let arg = self.eval(right)?;
+                let mut scope = self.env.child();
+                scope.set(param, arg);
+                Evaluator::new(&mut scope, source).eval(&body)
+            }
+            BinaryVariant::Add => {
+                self.eval_arithmetic(left, right, from, to, i64::checked_add, |a, b| a + b)
+            }
+            BinaryVariant::Sub => {
+                self.eval_arithmetic(left, right, from, to, i64::checked_sub, |a, b| a - b)
+            }
+            BinaryVariant::Mul => {
+                self.eval_arithmetic(left, right, from, to, i64::checked_mul, |a, b| a * b)
+            }
+            BinaryVariant::Pow => {
+                let left = self.eval(left)?;
+                let right = self.eval(right)?;
+                Ok(Value::Float(
+                    self.as_number(&left, from, to)?
+                        .powf(self.as_number(&right, from, to)?),
+                ))
+            }
+            BinaryVariant::Div => {
+                let left = self.eval(left)?;
+                let right = self.eval(right)?;
+                let right = self.as_number(&right, from, to)?;
+                if right == 0.0 {
+                    return Err(self.error(from, to, "division by zero".to_string()));
+                }
+                Ok(Value::Float(self.as_number(&left, from, to)? / right))
+            }
+            BinaryVariant::Eq => {
+                let left = self.eval(left)?;
+                let right = self.eval(right)?;
+                Ok(Value::Bool(self.values_equal(&left, &right, from, to)?))
+            }
+        }
+    }
+
+    fn eval_arithmetic(
+        &mut self,
+        left: &Expr,
+        right: &Expr,
+        from: usize,
+        to: usize,
+        int_op: impl Fn(i64, i64) -> Option<i64>,
+        float_op: impl Fn(f64, f64) -> f64,
+    ) -> Result<Value, Error> {
+        let left = self.eval(left)?;
+        let right = self.eval(right)?;
+        Ok(match (left, right) {
+            (Value::Int(left), Value::Int(right)) => Value::Int(
+                int_op(left, right)
+                    .ok_or_else(|| self.error(from, to, "integer overflow".to_string()))?,
+            ),
+            (left, right) => Value::Float(float_op(
+                self.as_number(&left, from, to)?,
+                self.as_number(&right, from, to)?,
+            )),
+        })
+    }
+
+    fn as_number(&self, value: &Value, from: usize, to: usize) -> Result<f64, Error> {
+        match value {
+            Value::Int(value) => Ok(*value as f64),
+            Value::Float(value) => Ok(*value),
+            Value::Bool(value) => Ok(if *value { 1.0 } else { 0.0 }),
+            Value::Function { .. } => {
+                Err(self.error(from, to, "cannot use a function as a number".to_string()))
+            }
+        }
+    }
+
+    fn values_equal(
+        &self,
+        left: &Value,
+        right: &Value,
+        from: usize,
+        to: usize,
+    ) -> Result<bool, Error> {
+        Ok(match (left, right) {
+            (Value::Bool(left), Value::Bool(right)) => left == right,
+            (Value::Function { .. }, _) | (_, Value::Function { .. }) => {
+                return Err(self.error(
+                    from,
+                    to,
+                    "cannot compare a function for equality".to_string(),
+                ))
+            }
+            _ => self.as_number(left, from, to)? == self.as_number(right, from, to)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn eval(source: &str) -> Result<Value, Error> {
+        let lexer = Lexer::new(source.chars().collect::<Vec<_>>());
+        let mut parser = Parser::new(source, lexer);
+        let (expr, errors) = parser.parse();
+        assert!(errors.is_empty(), "unexpected parse errors for {source:?}");
+        Evaluator::new(&mut Environment::new(), source).eval(&expr)
+    }
+
+    /// Evaluates each line against a shared environment, the way the REPL
+    /// does, and returns the last line's result.
+    fn eval_program(lines: &[&str]) -> Result<Value, Error> {
+        let mut env = Environment::new();
+        let mut result = None;
+        for line in lines {
+            let lexer = Lexer::new(line.chars().collect::<Vec<_>>());
+            let mut parser = Parser::new(line, lexer);
+            let (expr, errors) = parser.parse();
+            assert!(errors.is_empty(), "unexpected parse errors for {line:?}");
+            result = Some(Evaluator::new(&mut env, *line).eval(&expr)?);
+        }
+        Ok(result.expect("at least one line"))
+    }
+
+    #[test]
+    fn division_always_produces_a_float() {
+        assert_eq!(eval("3/2").unwrap(), Value::Float(1.5));
+    }
+
+    #[test]
+    fn mixed_int_and_float_operands_promote_to_float() {
+        assert_eq!(eval("1 + 2.0").unwrap(), Value::Float(3.0));
+    }
+
+    #[test]
+    fn division_by_zero_is_a_spanned_error() {
+        let error = eval("1/0").unwrap_err();
+        assert_eq!(error.message, "division by zero");
+    }
+
+    #[test]
+    fn unknown_identifier_reports_its_span() {
+        let error = eval("abc").unwrap_err();
+        assert_eq!(error.message, "unknown identifier 'abc'");
+        assert_eq!((error.from, error.to), (0, 2));
+    }
+
+    #[test]
+    fn integer_overflow_is_a_spanned_error_not_a_panic() {
+        let error = eval("9999999999 * 9999999999").unwrap_err();
+        assert_eq!(error.message, "integer overflow");
+    }
+
+    #[test]
+    fn integer_arithmetic_stays_integer_when_it_fits() {
+        assert_eq!(eval("2 + 3 * 4").unwrap(), Value::Int(14));
+    }
+
+    #[test]
+    fn a_free_variable_error_renders_against_the_function_s_defining_line() {
+        let definition = "fn add x => x + y";
+        let error = eval_program(&[definition, "add(3)"]).unwrap_err();
+        assert_eq!(error.message, "unknown identifier 'y'");
+        assert_eq!(error.source, definition);
+        let (from, to) = (error.from, error.to);
+        assert_eq!(&definition[from..=to], "y");
+    }
+
+    #[test]
+    fn a_defined_function_can_be_called_by_name() {
+        let result = eval_program(&["fn square x => x^2", "square(5)"]).unwrap();
+        assert_eq!(result, Value::Float(25.0));
+    }
+
+    #[test]
+    fn unary_minus_binds_looser_than_pow() {
+        // -2^2 parses as -(2^2), not (-2)^2.
+        assert_eq!(eval("-2^2").unwrap(), Value::Float(-4.0));
+    }
+
+    #[test]
+    fn pow_is_right_associative() {
+        // 2^3^2 parses as 2^(3^2) = 2^9 = 512, not (2^3)^2 = 64.
+        assert_eq!(eval("2^3^2").unwrap(), Value::Float(512.0));
+    }
+
+    #[test]
+    fn mul_binds_tighter_than_add() {
+        assert_eq!(eval("1 + 2 * 3").unwrap(), Value::Int(7));
+    }
+
+    #[test]
+    fn equal_binds_loosest() {
+        assert_eq!(eval("2 = 1 + 1").unwrap(), Value::Bool(true));
+    }
+}
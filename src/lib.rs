@@ -0,0 +1,4 @@
+pub mod diagnostics;
+pub mod eval;
+pub mod lexer;
+pub mod parser;
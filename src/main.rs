@@ -0,0 +1,53 @@
+use std::io::{self, Write};
+
+use campculator::eval::{Environment, Evaluator};
+use campculator::lexer::Lexer;
+use campculator::parser::{BinaryVariant, Expr, Parser};
+
+fn main() {
+    let mut env = Environment::new();
+    let stdin = io::stdin();
+
+    loop {
+        print!("> ");
+        io::stdout().flush().expect("should be able to flush stdout");
+
+        let mut line = String::new();
+        if stdin.read_line(&mut line).expect("should be able to read stdin") == 0 {
+            break;
+        }
+        let line = line.trim_end_matches('\n');
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let lexer = Lexer::new(line.chars().collect::<Vec<_>>());
+        let mut parser = Parser::new(line, lexer);
+        let (expr, errors) = parser.parse();
+        if !errors.is_empty() {
+            for error in &errors {
+                println!("{}", error.render(line));
+            }
+            continue;
+        }
+
+        // `=` at the top level is assignment rather than an equality check.
+        if let Expr::Binary(BinaryVariant::Eq, left, right, _, _) = &expr {
+            if let Expr::Id(name, _, _) = left.as_ref() {
+                match Evaluator::new(&mut env, line).eval(right) {
+                    Ok(value) => {
+                        println!("{name} = {value}");
+                        env.set(name.clone(), value);
+                    }
+                    Err(error) => println!("{}", error.render()),
+                }
+                continue;
+            }
+        }
+
+        match Evaluator::new(&mut env, line).eval(&expr) {
+            Ok(value) => println!("{value}"),
+            Err(error) => println!("{}", error.render()),
+        }
+    }
+}
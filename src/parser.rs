@@ -1,18 +1,35 @@
 use crate::lexer::{Lexer, Token, TokenVariant};
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
-    Int(i64),
-    Float(f64),
-    Id(String),
-    Binary(BinaryVariant, Box<Expr>, Box<Expr>),
+    Int(i64, usize, usize),
+    Float(f64, usize, usize),
+    Id(String, usize, usize),
+    Binary(BinaryVariant, Box<Expr>, Box<Expr>, usize, usize),
+    Lambda(String, Box<Expr>, usize, usize),
 }
 
+impl Expr {
+    pub fn from_to(&self) -> (usize, usize) {
+        match self {
+            Expr::Int(_, from, to)
+            | Expr::Float(_, from, to)
+            | Expr::Id(_, from, to)
+            | Expr::Binary(_, _, _, from, to)
+            | Expr::Lambda(_, _, from, to) => (*from, *to),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum BinaryVariant {
     Fn,
+    Call,
     Add,
     Sub,
     Mul,
     Div,
+    Pow,
     Eq,
 }
 
@@ -22,178 +39,310 @@ pub struct Error {
     pub message: String,
 }
 
+impl Error {
+    pub fn render(&self, source: &str) -> String {
+        crate::diagnostics::render_error(source, self.from, self.to, &self.message)
+    }
+}
+
 pub struct Parser<'text> {
     text: &'text str,
     lexer: Lexer,
     current: Option<Token>,
+    errors: Vec<Error>,
 }
 
 impl<'text> Parser<'text> {
     pub fn new(text: &'text str, lexer: Lexer) -> Self {
-        return Self {
+        let mut parser = Self {
             text,
             lexer,
             current: None,
+            errors: Vec::new(),
         };
+        parser.step();
+        parser
     }
 
-    pub fn parse(&mut self) -> Result<Expr, Error> {
-        self.parse_operand()
+    /// Parses a single expression, returning the best-effort tree alongside
+    /// every diagnostic collected along the way instead of bailing on the
+    /// first one.
+    pub fn parse(&mut self) -> (Expr, Vec<Error>) {
+        let expr = self.parse_expr();
+        if let Some(current) = &self.current {
+            let (from, to) = (current.from, current.to);
+            self.error(from, to, "unexpected trailing input".to_string());
+        }
+        (expr, std::mem::take(&mut self.errors))
+    }
+
+    fn error(&mut self, from: usize, to: usize, message: String) {
+        self.errors.push(Error { from, to, message });
+    }
+
+    /// Skips tokens until an operator or closing paren is reached, so a bad
+    /// operand doesn't take the rest of the expression down with it.
+    fn synchronize(&mut self) {
+        while let Some(token) = &self.current {
+            if matches!(
+                token.variant,
+                TokenVariant::Add
+                    | TokenVariant::Sub
+                    | TokenVariant::Mul
+                    | TokenVariant::Div
+                    | TokenVariant::Pow
+                    | TokenVariant::Equal
+                    | TokenVariant::RParen
+            ) {
+                return;
+            }
+            self.step();
+        }
     }
 
-    fn parse_operand(&mut self) -> Result<Expr, Error> {
+    fn parse_operand(&mut self) -> Expr {
         let Some(current) = &self.current else {
-            panic!("break shit get hit");
+            let at = self.text.len();
+            self.error(at, at, "expected an expression, got end of input".to_string());
+            return Expr::Int(0, at, at);
         };
         match current.variant {
-            TokenVariant::Int => Ok(Expr::Int(
-                self.text[current.from..=current.to]
+            TokenVariant::Int => {
+                let value = self.text[current.from..=current.to]
                     .parse()
-                    .expect("should not tokenize incorrect int"),
-            )),
-            TokenVariant::Float => Ok(Expr::Float(
-                self.text[current.from..=current.to]
+                    .expect("should not tokenize incorrect int");
+                let from = current.from;
+                let to = current.to;
+                self.step();
+                Expr::Int(value, from, to)
+            }
+            TokenVariant::Float => {
+                let value = self.text[current.from..=current.to]
                     .parse()
-                    .expect("should not tokenize incorrect float"),
-            )),
-            TokenVariant::Id => Ok(Expr::Id(self.text[current.from..=current.to].to_string())),
-            TokenVariant::LParen => {
+                    .expect("should not tokenize incorrect float");
+                let from = current.from;
+                let to = current.to;
                 self.step();
-                let expr = self.parse_expr();
-                let Some(closing) = &self.current else {
-                    self.step();
-                    return Err(Error {
-                        from: 0,
-                        to: 0,
-                        message: format!("expected LParen got None"),
-                    });
-                };
-                if !matches!(closing.variant, TokenVariant::LParen) {
-                    let err = Error {
-                        from: closing.from,
-                        to: closing.to,
-                        message: format!("expected LParen got {:?}", closing.variant),
-                    };
+                Expr::Float(value, from, to)
+            }
+            TokenVariant::Id => {
+                let from = current.from;
+                let to = current.to;
+                let name = self.text[from..=to].to_string();
+                self.step();
+                if matches!(
+                    self.current.as_ref().map(|t| &t.variant),
+                    Some(TokenVariant::LParen)
+                ) {
                     self.step();
-                    return Err(err);
+                    let arg = self.parse_expr();
+                    let closing = self.eat(TokenVariant::RParen);
+                    return Expr::Binary(
+                        BinaryVariant::Call,
+                        Box::new(Expr::Id(name, from, to)),
+                        Box::new(arg),
+                        from,
+                        closing.to,
+                    );
                 }
+                Expr::Id(name, from, to)
+            }
+            TokenVariant::LParen => {
                 self.step();
+                let expr = self.parse_expr();
+                match &self.current {
+                    Some(closing) if matches!(closing.variant, TokenVariant::RParen) => {
+                        self.step();
+                    }
+                    Some(closing) => {
+                        let (from, to, variant) =
+                            (closing.from, closing.to, closing.variant.clone());
+                        self.error(from, to, format!("expected RParen got {variant:?}"));
+                        self.synchronize();
+                    }
+                    None => {
+                        let at = self.text.len();
+                        self.error(at, at, "expected RParen got end of input".to_string());
+                    }
+                }
                 expr
             }
             TokenVariant::Fn => {
+                let from = current.from;
                 self.step();
-                let id = self.eat(TokenVariant::Id)?;
-                let id = Expr::Id(self.text[id.from..=id.to].to_string());
-                let expr = self.parse_expr()?;
-                return Ok(Expr::Binary(
-                    BinaryVariant::Fn,
-                    Box::new(id),
-                    Box::new(expr),
-                ));
+                let name = self.eat(TokenVariant::Id);
+                let name = Expr::Id(self.token_text(&name).to_string(), name.from, name.to);
+                let param = self.eat(TokenVariant::Id);
+                let param = self.token_text(&param).to_string();
+                self.eat(TokenVariant::Arrow);
+                let body = self.parse_expr();
+                let to = body.from_to().1;
+                let lambda = Expr::Lambda(param, Box::new(body), from, to);
+                Expr::Binary(BinaryVariant::Fn, Box::new(name), Box::new(lambda), from, to)
             }
-
-            TokenVariant::Add
-            | TokenVariant::Sub
-            | TokenVariant::Mul
-            | TokenVariant::Div
-            | TokenVariant::Pow
-            | TokenVariant::Equal
-            | TokenVariant::Arrow
-            | TokenVariant::RParen
-            | TokenVariant::Invalid => {
-                let err = Error {
-                    from: current.from,
-                    to: current.to,
-                    message: format!(
-                        "expected Int | Float | Id | RParen | Fn got '{:?}'",
-                        current.variant
-                    ),
-                };
+            _ => {
+                let from = current.from;
+                let to = current.to;
+                let message = format!(
+                    "expected Int | Float | Id | LParen | Fn got '{:?}'",
+                    current.variant
+                );
                 self.step();
-                Err(err)
+                self.error(from, to, message);
+                self.synchronize();
+                Expr::Int(0, from, to)
             }
         }
     }
 
-    fn eat(&mut self, variant: TokenVariant) -> Result<Token, Error> {
+    /// Slices the source text spanned by `token`, or `""` for the synthetic
+    /// end-of-input token `eat` returns when a required token is missing.
+    fn token_text(&self, token: &Token) -> &'text str {
+        if token.to < self.text.len() {
+            &self.text[token.from..=token.to]
+        } else {
+            ""
+        }
+    }
+
+    /// Consumes and returns the current token, recording a diagnostic
+    /// (without unwinding) if it is not `variant`. On end of input this
+    /// returns a synthetic token with `from == to == self.text.len()`
+    /// instead of short-circuiting; callers that slice the source with
+    /// the result must go through `token_text` rather than indexing
+    /// `self.text` directly, or they'll panic past the end of the string.
+    fn eat(&mut self, variant: TokenVariant) -> Token {
         let Some(current) = self.current.take() else {
-            return Err(Error {
-                from: 0,
-                to: 0,
-                message: format!("expected {variant:?} got None"),
-            });
+            let at = self.text.len();
+            self.error(at, at, format!("expected {variant:?} got end of input"));
+            return Token {
+                from: at,
+                to: at,
+                variant,
+            };
         };
 
         self.step();
 
         if current.variant != variant {
-            return Err(Error {
-                from: current.from,
-                to: current.to,
-                message: format!("expected {:?} got {:?}", variant, current.variant),
-            });
+            self.error(
+                current.from,
+                current.to,
+                format!("expected {:?} got {:?}", variant, current.variant),
+            );
         }
 
-        Ok(current)
+        current
     }
 
-    fn parse_expr(&mut self) -> Result<Expr, Error> {
-        self.parse_eq()
+    fn parse_expr(&mut self) -> Expr {
+        self.parse_expr_bp(0)
     }
 
-    fn parse_eq(&mut self) -> Result<Expr, Error> {
-        let left = self.parse_add_sub()?;
-        if self.current.is_none() {
-            return Ok(left);
-        }
-        let _ = self.eat(TokenVariant::Equal)?;
-        let right = self.parse_add_sub()?;
-        Ok(Expr::Binary(
-            BinaryVariant::Eq,
-            Box::new(left),
-            Box::new(right),
-        ))
-    }
-
-    fn parse_add_sub(&mut self) -> Result<Expr, Error> {
-        let mut left = self.parse_mul_div()?;
+    /// Precedence-climbing core: consumes an infix operator only while its
+    /// left binding power is at least `min_bp`, recursing with its right
+    /// binding power to parse the right-hand side. Equality binds loosest,
+    /// pow binds tightest and is right-associative (`left_bp > right_bp`).
+    fn parse_expr_bp(&mut self, min_bp: u8) -> Expr {
+        let mut left = self.parse_prefix();
         loop {
-            let variant = self.current.as_ref().map(|v| &v.variant).cloned();
-            if !matches!(variant, Some(TokenVariant::Add | TokenVariant::Sub)) {
-                break Ok(left);
+            let Some(variant) = self.current.as_ref().map(|t| t.variant.clone()) else {
+                break left;
+            };
+            let Some((left_bp, right_bp, binary_variant)) = infix_binding_power(&variant) else {
+                break left;
+            };
+            if left_bp < min_bp {
+                break left;
             }
             self.step();
-            let right = self.parse_mul_div()?;
-            if matches!(variant, Some(TokenVariant::Add)) {
-                left = Expr::Binary(BinaryVariant::Add, Box::new(left), Box::new(right))
-            } else {
-                left = Expr::Binary(BinaryVariant::Sub, Box::new(left), Box::new(right))
-            }
+            let right = self.parse_expr_bp(right_bp);
+            let from = left.from_to().0;
+            let to = right.from_to().1;
+            left = Expr::Binary(binary_variant, Box::new(left), Box::new(right), from, to);
         }
     }
 
-    fn parse_mul_div(&mut self) -> Result<Expr, Error> {
-        let mut left = self.parse_unary()?;
-        loop {
-            let variant = self.current.as_ref().map(|v| &v.variant).cloned();
-            if !matches!(variant, Some(TokenVariant::Mul | TokenVariant::Div)) {
-                break Ok(left);
-            }
-            self.step();
-            let right = self.parse_unary()?;
-            if matches!(variant, Some(TokenVariant::Mul)) {
-                left = Expr::Binary(BinaryVariant::Mul, Box::new(left), Box::new(right))
-            } else {
-                left = Expr::Binary(BinaryVariant::Div, Box::new(left), Box::new(right))
-            }
+    /// Unary minus is the only prefix operator; it binds tighter than
+    /// `*`/`/` but looser than `^`, so `-2^2` parses as `-(2^2)`.
+    fn parse_prefix(&mut self) -> Expr {
+        if !matches!(
+            self.current.as_ref().map(|t| &t.variant),
+            Some(TokenVariant::Sub)
+        ) {
+            return self.parse_operand();
         }
-    }
-
-    fn parse_unary(&mut self) -> Result<Expr, Error> {
-        todo!()
+        let from = self.current.as_ref().expect("just matched Some above").from;
+        self.step();
+        let operand = self.parse_expr_bp(UNARY_BINDING_POWER);
+        let to = operand.from_to().1;
+        Expr::Binary(
+            BinaryVariant::Sub,
+            Box::new(Expr::Int(0, from, from)),
+            Box::new(operand),
+            from,
+            to,
+        )
     }
 
     fn step(&mut self) {
         self.current = self.lexer.next();
     }
 }
+
+const UNARY_BINDING_POWER: u8 = 7;
+
+fn infix_binding_power(variant: &TokenVariant) -> Option<(u8, u8, BinaryVariant)> {
+    Some(match variant {
+        TokenVariant::Equal => (1, 2, BinaryVariant::Eq),
+        TokenVariant::Add => (3, 4, BinaryVariant::Add),
+        TokenVariant::Sub => (3, 4, BinaryVariant::Sub),
+        TokenVariant::Mul => (5, 6, BinaryVariant::Mul),
+        TokenVariant::Div => (5, 6, BinaryVariant::Div),
+        TokenVariant::Pow => (10, 9, BinaryVariant::Pow),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> (Expr, Vec<Error>) {
+        let lexer = Lexer::new(source.chars().collect::<Vec<_>>());
+        Parser::new(source, lexer).parse()
+    }
+
+    #[test]
+    fn a_bad_operand_collects_an_error_but_still_returns_a_tree() {
+        let (expr, errors) = parse("1 + @ + 2");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].message,
+            "expected Int | Float | Id | LParen | Fn got 'Invalid'"
+        );
+        assert!(matches!(expr, Expr::Binary(BinaryVariant::Add, ..)));
+    }
+
+    #[test]
+    fn multiple_bad_operands_are_all_recovered_from() {
+        let (_expr, errors) = parse("@ + @");
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn trailing_input_after_a_complete_expression_is_reported() {
+        let (expr, errors) = parse("5 5");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "unexpected trailing input");
+        assert_eq!((errors[0].from, errors[0].to), (2, 2));
+        assert_eq!(expr, Expr::Int(5, 0, 0));
+    }
+
+    #[test]
+    fn trailing_input_does_not_swallow_the_dropped_operand() {
+        let (_expr, errors) = parse("2 + 3 4");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "unexpected trailing input");
+    }
+}